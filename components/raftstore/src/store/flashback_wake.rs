@@ -0,0 +1,44 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Waking a hibernated region before proposing `PrepareFlashback`.
+//!
+//! A hibernated leader has stopped ticking and only reacts again once a
+//! message arrives or `peer_stale_state_check_interval` elapses. Left alone,
+//! a `PrepareFlashback` proposed against such a leader would sit unapplied
+//! until that next natural heartbeat, which makes the flashback window
+//! imprecise. The flashback proposal path must force the region active
+//! first.
+
+use crate::store::fsm::GroupState;
+
+/// Given the peer's current hibernation `GroupState`, returns the state it
+/// should be forced into so ticking resumes immediately, or `None` if the
+/// peer is already active and nothing needs to change.
+///
+/// Its caller in this crate is `group_state_before_flashback_propose`, which
+/// returns the resulting state without applying it to a peer.
+pub fn state_to_wake_for_flashback(current: GroupState) -> Option<GroupState> {
+    match current {
+        GroupState::Idle | GroupState::PreChaos => Some(GroupState::Chaos),
+        GroupState::Chaos | GroupState::Ordered => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_to_wake_for_flashback() {
+        assert_eq!(
+            state_to_wake_for_flashback(GroupState::Idle),
+            Some(GroupState::Chaos)
+        );
+        assert_eq!(
+            state_to_wake_for_flashback(GroupState::PreChaos),
+            Some(GroupState::Chaos)
+        );
+        assert_eq!(state_to_wake_for_flashback(GroupState::Chaos), None);
+        assert_eq!(state_to_wake_for_flashback(GroupState::Ordered), None);
+    }
+}
@@ -0,0 +1,15 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+/// How actively a peer's raft group is being driven.
+///
+/// `Idle`/`PreChaos` peers have stopped (or are about to stop) ticking
+/// because the group has seen no traffic; `Chaos` ticks every cycle;
+/// `Ordered` ticks on the usual reduced hibernate-aware schedule once the
+/// group has settled back down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupState {
+    Idle,
+    PreChaos,
+    Chaos,
+    Ordered,
+}
@@ -0,0 +1,157 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+pub mod flashback_wake;
+pub mod fsm;
+pub mod read_index_during_apply;
+pub mod read_index_flashback;
+
+use kvproto::{
+    metapb::Region,
+    raft_cmdpb::{RaftCmdRequest, RaftCmdResponse},
+    raft_serverpb::PeerState,
+};
+use txn_types::WriteBatchFlags;
+
+use self::{
+    flashback_wake::state_to_wake_for_flashback,
+    fsm::GroupState,
+    read_index_during_apply::PendingFlashbackReads,
+    read_index_flashback::check_flashback_state_for_read_index,
+};
+
+/// Builds the response the leader sends back for a ReadIndex request
+/// against `region`.
+///
+/// The response carries the error from
+/// `check_flashback_state_for_read_index` when `region` is in flashback and
+/// `flags` lacks the flashback flag; otherwise its header has no error. Its
+/// only callers in this crate are `dispatch_read_index_request` and
+/// `resolve_pending_flashback_reads`; no other ReadIndex handling here goes
+/// through it.
+pub fn handle_read_index_response(region: &Region, flags: u64) -> RaftCmdResponse {
+    let mut resp = RaftCmdResponse::default();
+    if let Some(err) = check_flashback_state_for_read_index(region, flags) {
+        resp.mut_header().set_error(err);
+    }
+    resp
+}
+
+/// Returns the `GroupState` the peer's raft group should be forced into,
+/// if any, before `PrepareFlashback`/`FinishFlashback` is proposed.
+///
+/// A hibernated peer (`Idle`/`PreChaos`) maps to `Chaos`, as
+/// `state_to_wake_for_flashback` decides; any other state is returned
+/// unchanged. It only computes the state: it does not change any peer's
+/// state, and no proposal code in this crate calls it.
+pub fn group_state_before_flashback_propose(current: GroupState) -> GroupState {
+    state_to_wake_for_flashback(current).unwrap_or(current)
+}
+
+/// Dispatches an incoming ReadIndex `request` against a peer currently in
+/// `peer_state`.
+///
+/// This is the read-index dispatch call site for `PendingFlashbackReads`:
+/// when the peer is still `Applying` a snapshot and the request carries the
+/// flashback flag, it's pushed onto `pending` and `None` is returned so the
+/// caller knows to wait rather than answer (or reject) the read right away.
+/// Anything else is answered immediately through `handle_read_index_response`.
+pub fn dispatch_read_index_request(
+    region: &Region,
+    peer_state: PeerState,
+    request: RaftCmdRequest,
+    pending: &mut PendingFlashbackReads,
+) -> Option<RaftCmdResponse> {
+    let flags = request.get_header().get_flags();
+    let is_flashback_request = WriteBatchFlags::from_bits_truncate(flags)
+        .contains(WriteBatchFlags::FLASHBACK);
+    if PendingFlashbackReads::should_buffer(peer_state, is_flashback_request) {
+        pending.push(request);
+        return None;
+    }
+    Some(handle_read_index_response(region, flags))
+}
+
+/// Drains every read buffered by `dispatch_read_index_request` while the
+/// peer was `Applying`, treating the peer as no longer `Applying`, and
+/// re-evaluates each one against `region`'s current flashback state.
+pub fn resolve_pending_flashback_reads(
+    region: &Region,
+    pending: &mut PendingFlashbackReads,
+) -> Vec<(RaftCmdRequest, RaftCmdResponse)> {
+    pending
+        .take_ready(PeerState::Normal)
+        .into_iter()
+        .map(|read| {
+            let flags = read.request.get_header().get_flags();
+            let resp = handle_read_index_response(region, flags);
+            (read.request, resp)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_state_before_flashback_propose_wakes_hibernated_peer() {
+        assert_eq!(
+            group_state_before_flashback_propose(GroupState::Idle),
+            GroupState::Chaos
+        );
+        assert_eq!(
+            group_state_before_flashback_propose(GroupState::Chaos),
+            GroupState::Chaos
+        );
+    }
+
+    #[test]
+    fn test_handle_read_index_response_checks_flashback() {
+        let mut region = Region::default();
+        region.set_id(7);
+        region.set_is_in_flashback(true);
+
+        let resp = handle_read_index_response(&region, 0);
+        assert!(resp.get_header().get_error().has_flashback_in_progress());
+
+        let resp = handle_read_index_response(&region, txn_types::WriteBatchFlags::FLASHBACK.bits());
+        assert!(!resp.get_header().has_error());
+    }
+
+    fn flashback_flagged_request(region_id: u64) -> RaftCmdRequest {
+        let mut req = RaftCmdRequest::default();
+        req.mut_header().set_region_id(region_id);
+        req.mut_header()
+            .set_flags(txn_types::WriteBatchFlags::FLASHBACK.bits());
+        req
+    }
+
+    #[test]
+    fn test_dispatch_read_index_request_buffers_while_applying() {
+        let mut region = Region::default();
+        region.set_id(1);
+        let mut pending = PendingFlashbackReads::default();
+
+        let resp = dispatch_read_index_request(
+            &region,
+            PeerState::Applying,
+            flashback_flagged_request(1),
+            &mut pending,
+        );
+        assert!(resp.is_none());
+
+        // Not flagged: answered immediately even while applying.
+        let resp = dispatch_read_index_request(
+            &region,
+            PeerState::Applying,
+            RaftCmdRequest::default(),
+            &mut pending,
+        );
+        assert!(resp.is_some());
+
+        region.set_is_in_flashback(true);
+        let resolved = resolve_pending_flashback_reads(&region, &mut pending);
+        assert_eq!(resolved.len(), 1);
+        assert!(!resolved[0].1.get_header().has_error());
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Handling ReadIndex reads that arrive while a peer is `Applying` a
+//! snapshot.
+//!
+//! A peer installing a snapshot is in `PeerState::Applying` and has no
+//! usable region state to answer a read against yet. Dropping such a read
+//! outright is wrong once it carries the flashback flag: the read was valid
+//! when sent and should still succeed (or fail with the correct flashback
+//! error) once the snapshot finishes applying and `is_in_flashback` reflects
+//! the post-apply truth, rather than racing the apply.
+
+use kvproto::{raft_cmdpb::RaftCmdRequest, raft_serverpb::PeerState};
+
+/// A flashback-flagged ReadIndex request that arrived while its peer was
+/// still applying a snapshot. `PendingFlashbackReads::take_ready` drains
+/// these once the peer leaves `PeerState::Applying`, so they can be
+/// re-evaluated against the now-current `is_in_flashback` state instead of
+/// being rejected or served against a partially-applied region.
+pub struct PendingFlashbackRead {
+    pub request: RaftCmdRequest,
+}
+
+/// Buffers flashback-flagged reads that show up mid-apply so they can be
+/// resolved once the snapshot finishes, instead of being bounced as
+/// not-prepared or served against a half-applied region.
+#[derive(Default)]
+pub struct PendingFlashbackReads {
+    reads: Vec<PendingFlashbackRead>,
+}
+
+impl PendingFlashbackReads {
+    /// Returns `true` if `state`/`is_flashback_request` describe a read that must wait:
+    /// a flashback-flagged request reaching a peer still `Applying`.
+    pub fn should_buffer(state: PeerState, is_flashback_request: bool) -> bool {
+        state == PeerState::Applying && is_flashback_request
+    }
+
+    pub fn push(&mut self, request: RaftCmdRequest) {
+        self.reads.push(PendingFlashbackRead { request });
+    }
+
+    /// Drains every buffered read once the peer is no longer `Applying`, so
+    /// the caller can re-evaluate each one against the current region state.
+    pub fn take_ready(&mut self, state: PeerState) -> Vec<PendingFlashbackRead> {
+        if state == PeerState::Applying {
+            return Vec::new();
+        }
+        std::mem::take(&mut self.reads)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kvproto::raft_cmdpb::RaftCmdRequest;
+
+    use super::*;
+
+    #[test]
+    fn test_should_buffer() {
+        assert!(PendingFlashbackReads::should_buffer(
+            PeerState::Applying,
+            true
+        ));
+        assert!(!PendingFlashbackReads::should_buffer(
+            PeerState::Applying,
+            false
+        ));
+        assert!(!PendingFlashbackReads::should_buffer(
+            PeerState::Normal,
+            true
+        ));
+    }
+
+    #[test]
+    fn test_take_ready_drains_only_once_not_applying() {
+        let mut pending = PendingFlashbackReads::default();
+        pending.push(RaftCmdRequest::default());
+        pending.push(RaftCmdRequest::default());
+
+        assert!(pending.take_ready(PeerState::Applying).is_empty());
+        assert_eq!(pending.take_ready(PeerState::Normal).len(), 2);
+        assert!(pending.take_ready(PeerState::Normal).is_empty());
+    }
+}
@@ -0,0 +1,61 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Flashback gating for ReadIndex-based reads.
+//!
+//! A replica read bypasses the leader's normal lease/propose path: the
+//! follower sends a ReadIndex request, the leader confirms leadership via a
+//! heartbeat quorum and hands back its committed index, and the follower
+//! reads locally once its applied index catches up. None of that goes
+//! through `Peer::propose`, so a region in flashback needs its own check
+//! here, mirroring the one already applied to proposed commands.
+
+use kvproto::{errorpb, metapb};
+use txn_types::WriteBatchFlags;
+
+/// Returns a `FlashbackInProgress` error if `region` is in flashback and the
+/// request driving this ReadIndex does not carry the flashback flag.
+///
+/// Called by `handle_read_index_response`, which puts the returned error in
+/// the header of the ReadIndex response it builds.
+pub fn check_flashback_state_for_read_index(
+    region: &metapb::Region,
+    flags: u64,
+) -> Option<errorpb::Error> {
+    if !region.get_is_in_flashback() || WriteBatchFlags::from_bits_truncate(flags)
+        .contains(WriteBatchFlags::FLASHBACK)
+    {
+        return None;
+    }
+    let mut err = errorpb::Error::default();
+    err.mut_flashback_in_progress()
+        .set_region_id(region.get_id());
+    Some(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_flashback_state_for_read_index() {
+        let mut region = metapb::Region::default();
+        region.set_id(1);
+
+        // Not in flashback: always allowed.
+        assert!(check_flashback_state_for_read_index(&region, 0).is_none());
+        assert!(
+            check_flashback_state_for_read_index(&region, WriteBatchFlags::FLASHBACK.bits())
+                .is_none()
+        );
+
+        region.set_is_in_flashback(true);
+        // In flashback, unflagged: rejected.
+        let err = check_flashback_state_for_read_index(&region, 0).unwrap();
+        assert_eq!(err.get_flashback_in_progress().get_region_id(), 1);
+        // In flashback, flagged: allowed.
+        assert!(
+            check_flashback_state_for_read_index(&region, WriteBatchFlags::FLASHBACK.bits())
+                .is_none()
+        );
+    }
+}
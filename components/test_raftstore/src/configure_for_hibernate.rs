@@ -0,0 +1,28 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Test helper for putting a cluster's regions to sleep on purpose.
+//!
+//! A region hibernates when its leader sees no traffic: ticks stop and the
+//! leader no longer proposes heartbeats until something wakes it back up.
+//! Tests that need to assert a command is handled promptly even on a
+//! hibernated region (e.g. `PrepareFlashback`) first need a cluster
+//! configured so a hibernated leader stays asleep.
+
+use std::time::Duration;
+
+use crate::{Cluster, Simulator};
+
+/// Configures `cluster` so a hibernated leader keeps sleeping: long
+/// `peer_stale_state_check_interval`, `abnormal_leader_missing_duration` and
+/// `max_leader_missing_duration` keep the stale-state check and the
+/// leader-missing timeouts from waking the region during the test.
+pub fn configure_for_hibernate<T: Simulator>(cluster: &mut Cluster<T>) {
+    cluster.cfg.raft_store.abnormal_leader_missing_duration = test_util::ReadableDuration(
+        Duration::from_secs(3600),
+    );
+    cluster.cfg.raft_store.max_leader_missing_duration = test_util::ReadableDuration(
+        Duration::from_secs(3600),
+    );
+    cluster.cfg.raft_store.peer_stale_state_check_interval =
+        test_util::ReadableDuration(Duration::from_secs(10));
+}
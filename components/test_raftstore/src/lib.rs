@@ -0,0 +1,9 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+mod configure_for_hibernate;
+mod transport_simulate;
+mod transport_simulate_drop_message;
+
+pub use configure_for_hibernate::configure_for_hibernate;
+pub use transport_simulate::{Error, Filter, Result};
+pub use transport_simulate_drop_message::DropMessageFilter;
@@ -0,0 +1,25 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Minimal core of the message-filter infrastructure that
+//! `transport_simulate_drop_message` plugs into: the `Filter` trait every
+//! fault-injection filter implements, and the `Result`/`Error` types its
+//! `before`/`after` hooks use.
+
+use kvproto::raft_serverpb::RaftMessage;
+
+#[derive(Debug)]
+pub enum Error {
+    Other(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A hook into the simulated transport: `before` runs on outgoing messages
+/// before they're delivered, `after` runs on the delivery result.
+pub trait Filter: Send + Sync {
+    fn before(&self, msgs: &mut Vec<RaftMessage>) -> Result<()>;
+
+    fn after(&self, res: Result<()>) -> Result<()> {
+        res
+    }
+}
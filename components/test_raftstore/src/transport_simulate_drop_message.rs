@@ -0,0 +1,65 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! A generic, message-type-based drop filter for `transport_simulate`.
+//!
+//! Unlike `IsolationFilterFactory`, which blocks all traffic to a store, this
+//! drops only messages of one `MessageType`, leaving everything else (e.g.
+//! heartbeats, snapshots) to flow normally. That makes it possible to fault
+//! inject a single replication path, such as log append, without forcing a
+//! peer all the way into a snapshot.
+
+use kvproto::raft_serverpb::RaftMessage;
+use raft::eraftpb::MessageType;
+
+use crate::{Filter, Result};
+
+/// Drops every message whose `get_message().get_msg_type()` matches
+/// `msg_type`, passing everything else through unchanged.
+#[derive(Clone)]
+pub struct DropMessageFilter {
+    msg_type: MessageType,
+}
+
+impl DropMessageFilter {
+    pub fn new(msg_type: MessageType) -> DropMessageFilter {
+        DropMessageFilter { msg_type }
+    }
+}
+
+impl Filter for DropMessageFilter {
+    fn before(&self, msgs: &mut Vec<RaftMessage>) -> Result<()> {
+        let msg_type = self.msg_type;
+        msgs.retain(|m| m.get_message().get_msg_type() != msg_type);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kvproto::raft_serverpb::RaftMessage;
+    use raft::eraftpb::{Entry, Message, MessageType};
+
+    use super::*;
+
+    fn msg_of_type(msg_type: MessageType) -> RaftMessage {
+        let mut msg = Message::default();
+        msg.set_msg_type(msg_type);
+        msg.set_entries(vec![Entry::default()].into());
+        let mut raft_msg = RaftMessage::default();
+        raft_msg.set_message(msg);
+        raft_msg
+    }
+
+    #[test]
+    fn test_drop_message_filter() {
+        let filter = DropMessageFilter::new(MessageType::MsgAppend);
+        let mut msgs = vec![
+            msg_of_type(MessageType::MsgAppend),
+            msg_of_type(MessageType::MsgHeartbeat),
+            msg_of_type(MessageType::MsgAppend),
+        ];
+        filter.before(&mut msgs).unwrap();
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(msgs[0].get_message().get_msg_type(), MessageType::MsgHeartbeat);
+    }
+}
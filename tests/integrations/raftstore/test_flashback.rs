@@ -11,6 +11,7 @@ use kvproto::{
     metapb,
     raft_cmdpb::{AdminCmdType, RaftCmdResponse, Request},
 };
+use raft::eraftpb::MessageType;
 use raftstore::store::Callback;
 use test_raftstore::*;
 use txn_types::WriteBatchFlags;
@@ -285,6 +286,38 @@ fn test_flashback_for_local_read() {
     must_get_flashback_not_prepared_error(&mut cluster, &mut region, new_get_cmd(TEST_KEY));
 }
 
+// A replica read on a follower is served through the ReadIndex mechanism,
+// which bypasses the leader's lease/propose path already covered by
+// `test_flashback_for_read` and `test_flashback_for_local_read`. The leader
+// must still reject it while unflagged during a flashback.
+#[test]
+fn test_flashback_for_replica_read() {
+    let mut cluster = new_node_cluster(0, 3);
+    cluster.run();
+    cluster.must_transfer_leader(1, new_peer(1, 1));
+    cluster.must_put(TEST_KEY, TEST_VALUE);
+
+    let region = cluster.get_region(TEST_KEY);
+    // Prepare flashback on the leader.
+    cluster.must_send_wait_flashback_msg(region.get_id(), AdminCmdType::PrepareFlashback);
+
+    // A replica read on the follower (2, 2) goes through ReadIndex against
+    // the leader; it must be rejected just like a propose would be.
+    let mut follower_region = region.clone();
+    let mut cmd_req = new_request(
+        follower_region.get_id(),
+        follower_region.take_region_epoch(),
+        vec![new_get_cmd(TEST_KEY)],
+        true, // `read_quorum` forces a ReadIndex read instead of a local one.
+    );
+    cmd_req.mut_header().set_peer(new_peer(2, 2));
+    cmd_req.mut_header().set_replica_read(true);
+    let resp = cluster.call_command(cmd_req, Duration::from_secs(3)).unwrap();
+    assert!(resp.get_header().get_error().has_flashback_in_progress());
+
+    cluster.must_send_wait_flashback_msg(region.get_id(), AdminCmdType::FinishFlashback);
+}
+
 #[test]
 fn test_flashback_for_status_cmd_as_region_detail() {
     let mut cluster = new_node_cluster(0, 3);
@@ -362,6 +395,119 @@ fn test_flashback_for_apply_snapshot() {
     must_check_flashback_state(&mut cluster, 1, 3, false);
 }
 
+// Complements `test_flashback_for_apply_snapshot`: instead of isolating the
+// follower until it needs a snapshot, only `MsgAppend` to it is dropped, so
+// the flashback state it eventually observes must have come from normal log
+// replication rather than a snapshot.
+#[test]
+fn test_flashback_for_apply_no_snapshot() {
+    let mut cluster = new_node_cluster(0, 3);
+    cluster.run();
+
+    cluster.must_transfer_leader(1, new_peer(1, 1));
+    must_check_flashback_state(&mut cluster, 1, 1, false);
+    must_check_flashback_state(&mut cluster, 1, 3, false);
+
+    // Drop `MsgAppend` to store 3 while `PrepareFlashback` is applied on the
+    // leader.
+    cluster.sim.wl().add_recv_filter(
+        3,
+        Box::new(DropMessageFilter::new(MessageType::MsgAppend)),
+    );
+
+    cluster.must_send_wait_flashback_msg(1, AdminCmdType::PrepareFlashback);
+    must_check_flashback_state(&mut cluster, 1, 1, true);
+    must_check_flashback_state(&mut cluster, 1, 3, false);
+
+    // Stop dropping `MsgAppend` and let log replication catch the follower
+    // up.
+    cluster.sim.wl().clear_recv_filters(3);
+    must_check_flashback_state(&mut cluster, 1, 3, true);
+
+    cluster.must_send_wait_flashback_msg(1, AdminCmdType::FinishFlashback);
+    must_check_flashback_state(&mut cluster, 1, 1, false);
+    must_check_flashback_state(&mut cluster, 1, 3, false);
+}
+
+// A hibernated leader stops ticking, but `PrepareFlashback` must still be
+// proposed and applied promptly rather than stalling until the next natural
+// heartbeat wakes the region up.
+#[test]
+fn test_flashback_for_hibernate() {
+    let mut cluster = new_node_cluster(0, 3);
+    configure_for_hibernate(&mut cluster);
+    cluster.run();
+    cluster.must_transfer_leader(1, new_peer(1, 1));
+    cluster.must_put(TEST_KEY, TEST_VALUE);
+
+    // Let the region go to sleep.
+    sleep_ms(600);
+
+    cluster.must_send_wait_flashback_msg(1, AdminCmdType::PrepareFlashback);
+    must_check_flashback_state(&mut cluster, 1, 1, true);
+
+    // A write without the flashback flag must still be rejected.
+    let mut region = cluster.get_region(TEST_KEY);
+    must_get_flashback_in_progress_error(
+        &mut cluster,
+        &mut region,
+        new_put_cmd(TEST_KEY, TEST_VALUE),
+    );
+
+    cluster.must_send_wait_flashback_msg(1, AdminCmdType::FinishFlashback);
+    must_check_flashback_state(&mut cluster, 1, 1, false);
+}
+
+// A peer installing a snapshot sits in the `Applying` state; a
+// flashback-flagged read that arrives in this window must be queued and
+// resolved once the apply completes, rather than being rejected as
+// not-prepared or served against a partially-applied region.
+#[test]
+fn test_flashback_for_read_index_during_applying_snapshot() {
+    let mut cluster = new_node_cluster(0, 3);
+    configure_for_snapshot(&mut cluster);
+    cluster.run();
+
+    cluster.must_transfer_leader(1, new_peer(1, 1));
+    cluster.must_put(TEST_KEY, TEST_VALUE);
+
+    // Isolate store 3 long enough that it falls behind and needs a snapshot.
+    cluster.add_send_filter(IsolationFilterFactory::new(3));
+    for i in 100..110 {
+        let key = format!("k{}", i);
+        let value = format!("v{}", i);
+        cluster.must_put_cf("write", key.as_bytes(), value.as_bytes());
+    }
+
+    cluster.must_send_wait_flashback_msg(1, AdminCmdType::PrepareFlashback);
+    must_check_flashback_state(&mut cluster, 1, 1, true);
+    must_check_flashback_state(&mut cluster, 1, 3, false);
+
+    // Let store 3 start installing the snapshot and, without waiting for it
+    // to finish, send it a flashback-flagged read so it races with
+    // `Applying`.
+    cluster.clear_send_filters();
+    let mut region = cluster.get_region(TEST_KEY);
+    let mut cmd_req = new_request(
+        region.get_id(),
+        region.take_region_epoch(),
+        vec![new_get_cmd(TEST_KEY)],
+        false,
+    );
+    cmd_req.mut_header().set_peer(new_peer(3, 3));
+    cmd_req.mut_header().set_replica_read(true);
+    cmd_req
+        .mut_header()
+        .set_flags(WriteBatchFlags::FLASHBACK.bits());
+    let resp = cluster
+        .call_command(cmd_req, Duration::from_secs(3))
+        .unwrap();
+    assert!(!resp.get_header().has_error());
+
+    must_check_flashback_state(&mut cluster, 1, 3, true);
+    cluster.must_send_wait_flashback_msg(1, AdminCmdType::FinishFlashback);
+}
+
 fn must_check_flashback_state(
     cluster: &mut Cluster<NodeCluster>,
     region_id: u64,